@@ -0,0 +1,124 @@
+//! An async variant of the blocking endpoint surface in the crate root.
+//!
+//! Each function here returns a [`BoxFut`](type.BoxFut.html) that performs the HTTP request and
+//! JSON decoding on a background thread rather than blocking the calling thread, so it can be
+//! driven by any async runtime. Because the returned values are plain `Future`s, independent
+//! calls (for example looking up a match's winner and fetching the leaderboard) can be raced or
+//! joined concurrently by whatever executor is driving them.
+//!
+//! The blocking functions in the crate root are implemented in terms of these, by running them to
+//! completion with [`block_on`](fn.block_on.html).
+use std::future::Future;
+use std::pin::Pin;
+use std::sync::{Arc, Mutex};
+use std::task::{Context, Poll, Wake, Waker};
+use std::thread;
+
+use error::ApiError;
+use {Account, GetMatchesResponse, GetPlayersResponse};
+
+/// A boxed future resolving to a `Result<T, ApiError>`, returned by every function in this
+/// module.
+pub type BoxFut<T> = Pin<Box<dyn Future<Output = Result<T, ApiError>> + Send>>;
+
+struct Shared<T> {
+    result: Mutex<Option<Result<T, ApiError>>>,
+    waker: Mutex<Option<Waker>>
+}
+
+/// A future resolved by a blocking operation running on a dedicated thread.
+struct BlockingFuture<T> {
+    shared: Arc<Shared<T>>
+}
+
+impl<T> Future for BlockingFuture<T> {
+    type Output = Result<T, ApiError>;
+
+    fn poll(self: Pin<&mut Self>, cx: &mut Context) -> Poll<Self::Output> {
+        let mut result = self.shared.result.lock().unwrap();
+        if let Some(result) = result.take() {
+            return Poll::Ready(result);
+        }
+        *self.shared.waker.lock().unwrap() = Some(cx.waker().clone());
+        Poll::Pending
+    }
+}
+
+/// Run `work` on a new thread and return a future that resolves once it finishes.
+fn spawn_blocking<T, F>(work: F) -> BoxFut<T>
+    where F: FnOnce() -> Result<T, ApiError> + Send + 'static,
+          T: Send + 'static
+{
+    let shared = Arc::new(Shared {
+        result: Mutex::new(None),
+        waker: Mutex::new(None)
+    });
+
+    let thread_shared = shared.clone();
+    thread::spawn(move || {
+        let result = work();
+        *thread_shared.result.lock().unwrap() = Some(result);
+        if let Some(waker) = thread_shared.waker.lock().unwrap().take() {
+            waker.wake();
+        }
+    });
+
+    Box::pin(BlockingFuture { shared: shared })
+}
+
+/// Block the current thread until `future` resolves.
+///
+/// This is what the blocking functions in the crate root use to run their async counterparts to
+/// completion.
+pub fn block_on<T>(mut future: BoxFut<T>) -> Result<T, ApiError> {
+    struct ThreadWaker(thread::Thread);
+
+    impl Wake for ThreadWaker {
+        fn wake(self: Arc<Self>) {
+            self.0.unpark();
+        }
+    }
+
+    let waker = Waker::from(Arc::new(ThreadWaker(thread::current())));
+    let mut cx = Context::from_waker(&waker);
+
+    loop {
+        match future.as_mut().poll(&mut cx) {
+            Poll::Ready(result) => return result,
+            Poll::Pending => thread::park()
+        }
+    }
+}
+
+/// Async variant of [`get_players`](../fn.get_players.html).
+pub fn get_players(account: &Account) -> BoxFut<GetPlayersResponse> {
+    let account = account.clone();
+    spawn_blocking(move || ::get_players_with_client(&account, account.client()))
+}
+
+/// Async variant of [`get_recent_matches_for_company`](../fn.get_recent_matches_for_company.html).
+pub fn get_recent_matches_for_company(account: &Account) -> BoxFut<GetMatchesResponse> {
+    let account = account.clone();
+    spawn_blocking(move || ::get_recent_matches_for_company_with_client(&account, account.client()))
+}
+
+/// Async variant of [`get_players_ids`](../fn.get_players_ids.html).
+pub fn get_players_ids(account: &Account, names: Vec<String>) -> BoxFut<Vec<u32>> {
+    let account = account.clone();
+    spawn_blocking(move || {
+        let names = names.iter().map(|n| &n[..]).collect();
+        ::get_players_ids_with_client(&account, account.client(), names)
+    })
+}
+
+/// Async variant of [`add_match`](../fn.add_match.html).
+pub fn add_match(account: &Account, winner_id: u32, loser_id: u32) -> BoxFut<()> {
+    let account = account.clone();
+    spawn_blocking(move || ::add_match_with_client(&account, account.client(), winner_id, loser_id))
+}
+
+/// Async variant of [`add_match_with_names`](../fn.add_match_with_names.html).
+pub fn add_match_with_names(account: &Account, winner: String, loser: String) -> BoxFut<()> {
+    let account = account.clone();
+    spawn_blocking(move || ::add_match_with_names(&account, &winner, &loser))
+}
@@ -0,0 +1,228 @@
+//! A pluggable HTTP client abstraction.
+//!
+//! All of the endpoint functions in the crate root go through the [`Client`](trait.Client.html)
+//! trait rather than calling into `hyper` directly. This means the HTTP stack used to talk to
+//! startuppong.com can be swapped out (a different hyper version, `reqwest`, a connection-pooling
+//! wrapper, or an in-memory fake for tests) without touching any of the endpoint logic.
+//!
+//! [`HyperClient`](struct.HyperClient.html) is the default implementation, backed by a plain
+//! `hyper::Client`, and is what all of the public functions use unless a `_with_client` variant
+//! is called explicitly.
+use std::io::Read;
+use std::str;
+use std::thread;
+use std::time::Duration;
+
+use hyper;
+use rustc_serialize::Decodable;
+use rustc_serialize::json;
+
+use error::{ApiError, ApiErrorBody};
+use rate_limit::{ClientConfig, RateLimiter};
+
+/// Something capable of making GET and POST requests.
+///
+/// Implement this trait to plug in an alternate HTTP stack, a shared connection pool, or a stub
+/// for testing.
+pub trait Client {
+    /// The response type produced by this client's requests.
+    type Response: Response;
+
+    /// Issue a GET request against `url`.
+    fn get(&self, url: &str) -> Result<Self::Response, ApiError>;
+
+    /// Issue a POST request against `url` with the given body and content type.
+    fn post(&self, url: &str, body: &str, content_type: &str) -> Result<Self::Response, ApiError>;
+}
+
+/// An HTTP response as seen by the rest of the crate.
+///
+/// Implementations just need to expose the status code and let the body be consumed once, either
+/// as a raw `String` or decoded directly as JSON.
+pub trait Response {
+    /// The HTTP status code of the response.
+    fn status(&self) -> u16;
+
+    /// Consume the response, returning its body as a `String`.
+    fn into_body(self) -> Result<String, ApiError>;
+
+    /// Consume the response, returning its body if the status was a success (2xx), or an
+    /// `ApiError::Api` otherwise.
+    ///
+    /// On a non-success status, the body is decoded as an [`ApiErrorBody`](../error/struct.ApiErrorBody.html)
+    /// to build the error's message, falling back to the raw body if it isn't in that shape.
+    fn into_checked_body(self) -> Result<String, ApiError>
+        where Self: Sized
+    {
+        let status = self.status();
+        let body = try!(self.into_body());
+
+        if status >= 200 && status < 300 {
+            Ok(body)
+        } else {
+            let message = json::decode::<ApiErrorBody>(&body)
+                .map(|err| err.message)
+                .unwrap_or(body);
+            Err(ApiError::Api { status: status, message: message })
+        }
+    }
+
+    /// Consume the response, decoding its body as JSON.
+    ///
+    /// Checks the status first, via [`into_checked_body`](#method.into_checked_body).
+    fn into_json<T>(self) -> Result<T, ApiError>
+        where Self: Sized, T: Decodable
+    {
+        let body = try!(self.into_checked_body());
+        Ok(try!(json::decode::<T>(&body)))
+    }
+}
+
+/// The default [`Client`](trait.Client.html) implementation, backed by `hyper::Client`.
+///
+/// Every request first acquires a token from a [`RateLimiter`](../rate_limit/struct.RateLimiter.html),
+/// and a `429 Too Many Requests` response is retried (honoring a `Retry-After` header when
+/// present) up to `ClientConfig::max_retries` times before giving up with
+/// `ApiError::RateLimited`.
+pub struct HyperClient {
+    inner: hyper::Client,
+    limiter: RateLimiter,
+    max_retries: u32
+}
+
+impl HyperClient {
+    /// Create a new `HyperClient` wrapping a fresh `hyper::Client`, using the default
+    /// `ClientConfig`.
+    ///
+    /// Constructing a `HyperClient` once and reusing it (rather than calling this for every
+    /// request) lets hyper reuse its connection pool across calls, and lets the rate limiter see
+    /// every request made against an account.
+    pub fn new() -> HyperClient {
+        HyperClient::with_config(ClientConfig::default())
+    }
+
+    /// Create a new `HyperClient` with a custom `ClientConfig`.
+    pub fn with_config(config: ClientConfig) -> HyperClient {
+        HyperClient {
+            inner: hyper::Client::new(),
+            max_retries: config.max_retries,
+            limiter: RateLimiter::new(&config)
+        }
+    }
+
+    /// Send a request built by `make_request`, retrying on `429` responses until one succeeds,
+    /// `max_retries` is exhausted, or a non-429 status is returned.
+    fn send<F>(&self, mut make_request: F) -> Result<HyperResponse, ApiError>
+        where F: FnMut() -> hyper::error::Result<hyper::client::Response>
+    {
+        let mut attempt = 0;
+
+        loop {
+            self.limiter.acquire();
+
+            let mut res = try!(make_request());
+            let status = res.status.to_u16();
+
+            if status != 429 {
+                let mut body = String::new();
+                try!(res.read_to_string(&mut body));
+                return Ok(HyperResponse { status: status, body: body });
+            }
+
+            if attempt >= self.max_retries {
+                return Err(ApiError::RateLimited);
+            }
+
+            thread::sleep(retry_after(&res).unwrap_or(self.limiter.window()));
+            attempt += 1;
+        }
+    }
+}
+
+impl Client for HyperClient {
+    type Response = HyperResponse;
+
+    fn get(&self, url: &str) -> Result<HyperResponse, ApiError> {
+        self.send(|| self.inner.get(url).send())
+    }
+
+    fn post(&self, url: &str, body: &str, content_type: &str) -> Result<HyperResponse, ApiError> {
+        use hyper::header::ContentType;
+
+        self.send(|| {
+            self.inner.post(url)
+                      .header(ContentType(content_type.parse().unwrap()))
+                      .body(body)
+                      .send()
+        })
+    }
+}
+
+/// Parse a `Retry-After` header (in seconds) off of a `429` response, if present.
+fn retry_after(res: &hyper::client::Response) -> Option<Duration> {
+    res.headers.get_raw("Retry-After")
+        .and_then(|values| values.get(0))
+        .and_then(|value| str::from_utf8(value).ok())
+        .and_then(|value| value.trim().parse::<u64>().ok())
+        .map(Duration::from_secs)
+}
+
+/// The [`Response`](trait.Response.html) produced by [`HyperClient`](struct.HyperClient.html).
+pub struct HyperResponse {
+    status: u16,
+    body: String
+}
+
+impl Response for HyperResponse {
+    fn status(&self) -> u16 {
+        self.status
+    }
+
+    fn into_body(self) -> Result<String, ApiError> {
+        Ok(self.body)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{Client, Response};
+    use error::ApiError;
+
+    /// A `Client` that always returns a fixed body, for exercising endpoint functions without
+    /// making a real HTTP request.
+    struct StubClient {
+        body: &'static str
+    }
+
+    struct StubResponse {
+        body: &'static str
+    }
+
+    impl Response for StubResponse {
+        fn status(&self) -> u16 { 200 }
+
+        fn into_body(self) -> Result<String, ApiError> {
+            Ok(self.body.to_owned())
+        }
+    }
+
+    impl Client for StubClient {
+        type Response = StubResponse;
+
+        fn get(&self, _url: &str) -> Result<StubResponse, ApiError> {
+            Ok(StubResponse { body: self.body })
+        }
+
+        fn post(&self, _url: &str, _body: &str, _content_type: &str) -> Result<StubResponse, ApiError> {
+            Ok(StubResponse { body: self.body })
+        }
+    }
+
+    #[test]
+    fn into_json_decodes_stub_response_body() {
+        let client = StubClient { body: r#"{"players": []}"# };
+        let res = client.get("http://example.com").unwrap();
+        let decoded: ::GetPlayersResponse = res.into_json().unwrap();
+        assert_eq!(decoded.players().len(), 0);
+    }
+}
@@ -8,6 +8,13 @@ use std::error::Error;
 
 use rustc_serialize::json;
 
+/// The shape of the JSON error body the API returns alongside a non-2xx status code.
+#[derive(Debug, RustcDecodable)]
+pub struct ApiErrorBody {
+    pub code: String,
+    pub message: String
+}
+
 /// The error type returned in a startuppong `Result`.
 #[derive(Debug)]
 pub enum ApiError {
@@ -18,7 +25,13 @@ pub enum ApiError {
     /// Error reading response
     Io(io::Error),
     /// Response JSON could not be decoded
-    JsonDecoding(json::DecoderError)
+    JsonDecoding(json::DecoderError),
+    /// The API responded with a non-2xx status. `message` is taken from the response's JSON
+    /// error body when present, or the raw body otherwise.
+    Api { status: u16, message: String },
+    /// A request was retried after repeated `429 Too Many Requests` responses until the
+    /// configured maximum number of retries was reached.
+    RateLimited
 }
 
 impl Error for ApiError {
@@ -28,6 +41,8 @@ impl Error for ApiError {
             ApiError::Http(ref err) => err.description(),
             ApiError::Io(ref err) => err.description(),
             ApiError::JsonDecoding(ref err) => err.description(),
+            ApiError::Api { .. } => "The API responded with an error",
+            ApiError::RateLimited => "Exhausted retries after being rate limited",
         }
     }
 
@@ -37,6 +52,8 @@ impl Error for ApiError {
             ApiError::Http(ref err) => Some(err),
             ApiError::Io(ref err) => Some(err),
             ApiError::JsonDecoding(ref err) => Some(err),
+            ApiError::Api { .. } => None,
+            ApiError::RateLimited => None,
         }
     }
 }
@@ -48,6 +65,8 @@ impl fmt::Display for ApiError {
             ApiError::Http(ref err) => write!(f, "Http error: {}", err),
             ApiError::Io(ref err) => write!(f, "Io error: {}", err),
             ApiError::JsonDecoding(ref err) => write!(f, "JsonDecoding error: {}", err),
+            ApiError::Api { status, ref message } => write!(f, "Api error ({}): {}", status, message),
+            ApiError::RateLimited => write!(f, "Exhausted retries after being rate limited"),
         }
     }
 }
@@ -6,6 +6,12 @@
 //!
 //! Sign up for an account at [startuppong.com](http://www.startuppong.com).
 //!
+//! Construct one [`Account`](struct.Account.html) and reuse it for every call you make: it owns
+//! the `HyperClient` (and, with it, hyper's connection pool and the rate limiter) used by the
+//! endpoint functions below, and that client is shared across calls rather than rebuilt per
+//! request. The `_with_client` variants are the exception: they make their requests through the
+//! `Client` passed to them instead.
+//!
 //! # Examples
 //! ```no_run
 //! use startuppong::Account;
@@ -28,33 +34,57 @@
 extern crate rustc_serialize;
 extern crate hyper;
 extern crate mime;
+extern crate url;
 
 use std::env;
-use std::io::Read;
+use std::fmt;
+use std::sync::Arc;
 
-use hyper::header::ContentType;
-use rustc_serialize::json;
+use rustc_serialize::Encodable;
+use url::form_urlencoded;
 
 /// Error types, From impls, etc
 pub mod error;
 use error::ApiError;
 
+/// Pluggable HTTP client abstraction (`Client`, `Response`, and the default `HyperClient` impl)
+pub mod client;
+use client::{Client, HyperClient, Response};
+
+/// Token-bucket rate limiting used by `HyperClient`
+pub mod rate_limit;
+use rate_limit::ClientConfig;
+
+/// Async variant of the endpoint surface below, plus [`block_on`](async_client/fn.block_on.html)
+/// for running one of its futures to completion.
+pub mod async_client;
+
 /// An account is necessary to make requests against the API.
 ///
-/// This struct holds your account ID and access key. It is a required argument to all of the API
-/// methods.
-#[derive(Debug, RustcEncodable, Clone)]
+/// This struct holds your account ID and access key, along with the `HyperClient` used by the
+/// default (non-`_with_client`) functions in this crate. That client is shared (via an internal
+/// `Arc`) across every call made through a given `Account`, so its rate limiter actually sees and
+/// throttles consecutive requests instead of starting over with a full token bucket each time.
+#[derive(Clone)]
 pub struct Account {
     api_account_id: String,
-    api_access_key: String
+    api_access_key: String,
+    client: Arc<HyperClient>
 }
 
 impl Account {
-    /// Create a new Account
+    /// Create a new Account, with the default `ClientConfig`.
     pub fn new(id: String, key: String) -> Account {
+        Account::with_config(id, key, ClientConfig::default())
+    }
+
+    /// Create a new Account whose default client uses the given `ClientConfig`, to tune rate
+    /// limiting and retry behavior without reaching for the lower-level `_with_client` functions.
+    pub fn with_config(id: String, key: String, config: ClientConfig) -> Account {
         Account {
             api_account_id: id,
-            api_access_key: key
+            api_access_key: key,
+            client: Arc::new(HyperClient::with_config(config))
         }
     }
 
@@ -76,6 +106,31 @@ impl Account {
     pub fn key(&self) -> &str {
         &self.api_access_key[..]
     }
+
+    /// The `HyperClient` shared by every default (non-`_with_client`) call made through this
+    /// `Account`.
+    pub fn client(&self) -> &HyperClient {
+        &self.client
+    }
+}
+
+impl fmt::Debug for Account {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        f.debug_struct("Account")
+            .field("api_account_id", &self.api_account_id)
+            .field("api_access_key", &self.api_access_key)
+            .finish()
+    }
+}
+
+impl rustc_serialize::Encodable for Account {
+    fn encode<S: rustc_serialize::Encoder>(&self, s: &mut S) -> Result<(), S::Error> {
+        s.emit_struct("Account", 2, |s| {
+            try!(s.emit_struct_field("api_account_id", 0, |s| self.api_account_id.encode(s)));
+            try!(s.emit_struct_field("api_access_key", 1, |s| self.api_access_key.encode(s)));
+            Ok(())
+        })
+    }
 }
 
 /// A person on the ladder
@@ -145,7 +200,17 @@ impl GetMatchesResponse {
 /// endpoint and do a linear search for each name in names. The returned ids can be used as
 /// arguments to the add_match and other APIs requiring player IDs
 pub fn get_players_ids(account: &Account, names: Vec<&str>) -> Result<Vec<u32>, ApiError> {
-    let players = try!(get_players(account)).players();
+    let names = names.into_iter().map(|n| n.to_owned()).collect();
+    async_client::block_on(async_client::get_players_ids(account, names))
+}
+
+/// Get ids for players, using the given `Client` to make the request.
+///
+/// See [`get_players_ids`](fn.get_players_ids.html) for details.
+pub fn get_players_ids_with_client<C: Client>(account: &Account, client: &C, names: Vec<&str>)
+    -> Result<Vec<u32>, ApiError>
+{
+    let players = try!(get_players_with_client(account, client)).players();
     let mut ids = Vec::with_capacity(names.len());
 
     for name in &names {
@@ -166,35 +231,98 @@ pub fn get_players_ids(account: &Account, names: Vec<&str>) -> Result<Vec<u32>,
     Ok(ids)
 }
 
+/// Try to get ids for players, without failing when some names don't resolve.
+///
+/// Unlike [`get_players_ids`](fn.get_players_ids.html), a name that doesn't match any player
+/// doesn't abort the whole lookup: the returned `Vec` is the same length as `names`, with `None`
+/// in the position of any name that didn't match.
+pub fn try_get_players_ids(account: &Account, names: Vec<&str>) -> Result<Vec<Option<u32>>, ApiError> {
+    try_get_players_ids_with_client(account, account.client(), names)
+}
+
+/// Try to get ids for players, using the given `Client` to make the request.
+///
+/// See [`try_get_players_ids`](fn.try_get_players_ids.html) for details.
+pub fn try_get_players_ids_with_client<C: Client>(account: &Account, client: &C, names: Vec<&str>)
+    -> Result<Vec<Option<u32>>, ApiError>
+{
+    let players = try!(get_players_with_client(account, client)).players();
+
+    Ok(names.iter().map(|name| {
+        players.iter().find(|player| player.name.contains(name)).map(|player| player.id)
+    }).collect())
+}
+
+/// Look up a single player by name.
+///
+/// Returns `Ok(None)` rather than erroring when no player's name matches. Uses the same
+/// case-sensitive substring matching as [`get_players_ids`](fn.get_players_ids.html).
+pub fn find_player(account: &Account, name: &str) -> Result<Option<Player>, ApiError> {
+    find_player_with_client(account, account.client(), name)
+}
+
+/// Look up a single player by name, using the given `Client` to make the request.
+///
+/// See [`find_player`](fn.find_player.html) for details.
+pub fn find_player_with_client<C: Client>(account: &Account, client: &C, name: &str)
+    -> Result<Option<Player>, ApiError>
+{
+    let players = try!(get_players_with_client(account, client)).players();
+    Ok(players.into_iter().find(|player| player.name.contains(name)))
+}
+
 /// Return all players associated with the given account
 ///
 /// Wraps `/api/v1/get_players`
 pub fn get_players(account: &Account) -> Result<GetPlayersResponse, ApiError> {
-    let url = format!("http://www.startuppong.com/api/v1/get_players\
-                      ?api_account_id={}&api_access_key={}", account.id(), account.key());
-    get::<GetPlayersResponse>(&url)
+    async_client::block_on(async_client::get_players(account))
+}
+
+/// Return all players associated with the given account, using the given `Client` to make the
+/// request.
+///
+/// See [`get_players`](fn.get_players.html) for details. Sharing a single `Client` across calls
+/// lets it reuse connections, and a stub `Client` can be substituted in tests.
+pub fn get_players_with_client<C: Client>(account: &Account, client: &C)
+    -> Result<GetPlayersResponse, ApiError>
+{
+    let query = form_urlencoded::Serializer::new(String::new())
+        .append_pair("api_account_id", account.id())
+        .append_pair("api_access_key", account.key())
+        .finish();
+    let url = format!("http://www.startuppong.com/api/v1/get_players?{}", query);
+    get(client, &url)
 }
 
 /// Return most recent matches on the given account
 ///
 /// Wraps `/api/v1/get_recent_matches_for_company`
 pub fn get_recent_matches_for_company(account: &Account) -> Result<GetMatchesResponse, ApiError> {
-    let url = format!("http://www.startuppong.com/api/v1/get_recent_matches_for_company\
-                      ?api_account_id={}&api_access_key={}", account.id(), account.key());
-    get::<GetMatchesResponse>(&url)
+    async_client::block_on(async_client::get_recent_matches_for_company(account))
+}
+
+/// Return most recent matches on the given account, using the given `Client` to make the
+/// request.
+///
+/// See [`get_recent_matches_for_company`](fn.get_recent_matches_for_company.html) for details.
+pub fn get_recent_matches_for_company_with_client<C: Client>(account: &Account, client: &C)
+    -> Result<GetMatchesResponse, ApiError>
+{
+    let query = form_urlencoded::Serializer::new(String::new())
+        .append_pair("api_account_id", account.id())
+        .append_pair("api_access_key", account.key())
+        .finish();
+    let url = format!("http://www.startuppong.com/api/v1/get_recent_matches_for_company?{}", query);
+    get(client, &url)
 }
 
 /// Helper for retrieving a resource
 ///
 /// `get` assumes that the http response is JSON formatted, and the parameterized type T
 /// implements rustc_serialize::Decodable.
-fn get<T>(url: &str) -> Result<T, ApiError>
-    where T: rustc_serialize::Decodable {
-    let mut client = hyper::Client::new();
-    let mut res = try!(client.get(&url[..]).send());
-    let mut body = String::new();
-    try!(res.read_to_string(&mut body));
-    Ok(try!(json::decode::<T>(&body)))
+fn get<C, T>(client: &C, url: &str) -> Result<T, ApiError>
+    where C: Client, T: rustc_serialize::Decodable {
+    try!(client.get(url)).into_json::<T>()
 }
 
 /// Add a match
@@ -203,13 +331,24 @@ fn get<T>(url: &str) -> Result<T, ApiError>
 /// [add_match_with_names](fn.add_match_with_names.html) for a potentially easier to consume API.
 /// This method wraps the `/api/v1/add_match` endpoint.
 pub fn add_match(account: &Account, winner_id: u32, loser_id: u32) -> Result<(), ApiError> {
-    let mut client = hyper::Client::new();
-    let data = format!("api_account_id={}&api_access_key={}&winner_id={}&loser_id={}",
-                       account.id(), account.key(), winner_id, loser_id);
+    async_client::block_on(async_client::add_match(account, winner_id, loser_id))
+}
+
+/// Add a match, using the given `Client` to make the request.
+///
+/// See [`add_match`](fn.add_match.html) for details.
+pub fn add_match_with_client<C: Client>(account: &Account, client: &C, winner_id: u32, loser_id: u32)
+    -> Result<(), ApiError>
+{
+    let data = form_urlencoded::Serializer::new(String::new())
+        .append_pair("api_account_id", account.id())
+        .append_pair("api_access_key", account.key())
+        .append_pair("winner_id", &winner_id.to_string())
+        .append_pair("loser_id", &loser_id.to_string())
+        .finish();
     let url = "http://www.startuppong.com/api/v1/add_match";
-    try!(client.post(&url[..])
-               .header(ContentType("application/x-www-form-urlencoded".parse().unwrap()))
-               .body(&data).send());
+    let res = try!(client.post(url, &data, "application/x-www-form-urlencoded"));
+    try!(res.into_checked_body());
 
     Ok(())
 }
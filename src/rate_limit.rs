@@ -0,0 +1,81 @@
+//! Client-side rate limiting for [`HyperClient`](../client/struct.HyperClient.html).
+//!
+//! [`RateLimiter`](struct.RateLimiter.html) is a token bucket: `max_requests` tokens are handed
+//! out per `window`, and a call to [`acquire`](struct.RateLimiter.html#method.acquire) blocks
+//! until one is available. It's deliberately simple, since its only job is to keep the client
+//! from hammering startuppong.com when a caller makes many requests in a tight loop.
+use std::sync::Mutex;
+use std::thread;
+use std::time::{Duration, Instant};
+
+/// Tunable rate-limiting and retry behavior for a [`HyperClient`](../client/struct.HyperClient.html).
+#[derive(Debug, Clone)]
+pub struct ClientConfig {
+    /// Maximum number of requests allowed per `window`.
+    pub max_requests: u32,
+    /// The length of the rate-limiting window.
+    pub window: Duration,
+    /// Maximum number of times to retry a request after a `429 Too Many Requests` response
+    /// before giving up with `ApiError::RateLimited`.
+    pub max_retries: u32
+}
+
+impl Default for ClientConfig {
+    /// 10 requests per second, retried up to 3 times when rate limited.
+    fn default() -> ClientConfig {
+        ClientConfig {
+            max_requests: 10,
+            window: Duration::from_secs(1),
+            max_retries: 3
+        }
+    }
+}
+
+struct Bucket {
+    tokens: u32,
+    window_start: Instant
+}
+
+/// A token-bucket rate limiter.
+pub struct RateLimiter {
+    max_requests: u32,
+    window: Duration,
+    bucket: Mutex<Bucket>
+}
+
+impl RateLimiter {
+    /// Create a limiter that allows `config.max_requests` requests per `config.window`.
+    pub fn new(config: &ClientConfig) -> RateLimiter {
+        RateLimiter {
+            max_requests: config.max_requests,
+            window: config.window,
+            bucket: Mutex::new(Bucket { tokens: config.max_requests, window_start: Instant::now() })
+        }
+    }
+
+    /// The configured window length, used as a fallback retry delay when a `429` response carries
+    /// no `Retry-After` header.
+    pub fn window(&self) -> Duration {
+        self.window
+    }
+
+    /// Block the current thread until a token is available, then consume it.
+    pub fn acquire(&self) {
+        loop {
+            {
+                let mut bucket = self.bucket.lock().unwrap();
+                if bucket.window_start.elapsed() >= self.window {
+                    bucket.tokens = self.max_requests;
+                    bucket.window_start = Instant::now();
+                }
+
+                if bucket.tokens > 0 {
+                    bucket.tokens -= 1;
+                    return;
+                }
+            }
+
+            thread::sleep(self.window);
+        }
+    }
+}